@@ -0,0 +1,326 @@
+//! Configurable base85 alphabets and the variants (RFC 1924, Ascii85, Z85) built from them.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{Error, Result};
+
+const INVALID_DIGIT: u8 = 0xFF;
+
+/// An 85-symbol table mapping base85 "digits" (0-84) to bytes and back.
+///
+/// Built from 85 symbols, each of which must be a distinct printable ASCII byte
+/// (`0x21..=0x7E`); [`Alphabet::new`] rejects anything else.
+#[derive(Debug, Clone)]
+pub struct Alphabet {
+    encode_table: [u8; 85],
+    decode_table: [u8; 256],
+}
+
+impl Alphabet {
+    /// Builds an alphabet from 85 symbols, in the order they represent digits 0 through 84.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidAlphabetSymbol`] if a symbol isn't printable ASCII, or
+    /// [`Error::DuplicateAlphabetSymbol`] if a symbol appears more than once.
+    pub fn new(symbols: [u8; 85]) -> Result<Alphabet> {
+        let mut decode_table = [INVALID_DIGIT; 256];
+        for (digit, &c) in symbols.iter().enumerate() {
+            if !(0x21..=0x7E).contains(&c) {
+                return Err(Error::InvalidAlphabetSymbol(c));
+            }
+            if decode_table[c as usize] != INVALID_DIGIT {
+                return Err(Error::DuplicateAlphabetSymbol(c));
+            }
+            decode_table[c as usize] = digit as u8;
+        }
+        Ok(Alphabet {
+            encode_table: symbols,
+            decode_table,
+        })
+    }
+
+    /// Builds an alphabet from `symbols` known at compile time to be valid, panicking
+    /// otherwise. Used for the crate's predefined variants, so their validity is checked once
+    /// during compilation instead of on every call to [`Engine::rfc1924`] and friends.
+    pub(crate) const fn new_const(symbols: [u8; 85]) -> Alphabet {
+        let mut decode_table = [INVALID_DIGIT; 256];
+        let mut digit = 0;
+        while digit < 85 {
+            let c = symbols[digit];
+            assert!(
+                c >= 0x21 && c <= 0x7E,
+                "alphabet symbol is not printable ASCII"
+            );
+            assert!(
+                decode_table[c as usize] == INVALID_DIGIT,
+                "duplicate alphabet symbol"
+            );
+            decode_table[c as usize] = digit as u8;
+            digit += 1;
+        }
+        Alphabet {
+            encode_table: symbols,
+            decode_table,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn encode_digit(&self, digit: u8) -> u8 {
+        self.encode_table[digit as usize]
+    }
+
+    /// Looks up the digit (0-84) a symbol represents, or `None` if it isn't in this alphabet.
+    #[inline]
+    pub(crate) fn decode_digit(&self, c: u8) -> Option<u8> {
+        match self.decode_table[c as usize] {
+            INVALID_DIGIT => None,
+            digit => Some(digit),
+        }
+    }
+}
+
+/// The RFC 1924 alphabet, used both by [`Engine::rfc1924`] and by the crate-root free functions
+/// ([`crate::encode`], [`crate::decode_slice`], etc).
+pub(crate) fn rfc1924_alphabet() -> &'static Alphabet {
+    const ALPHABET: Alphabet = Alphabet::new_const(
+        *b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~",
+    );
+    &ALPHABET
+}
+
+/// Which base85 variant an [`Engine`] implements. This only affects the framing/compression
+/// wrapped around the core digit groups; the group math is driven entirely by the alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// RFC 1924, the crate's original and default encoding. No framing or compression.
+    Rfc1924,
+    /// Adobe/PostScript Ascii85. An all-zero 4-byte group is compressed to `z`, and the whole
+    /// output is wrapped in `<~`/`~>` delimiters.
+    Ascii85,
+    /// ZeroMQ's Z85, as used by CurveZMQ. No framing or compression.
+    Z85,
+}
+
+/// A base85 variant: an [`Alphabet`] paired with the [`Variant`] rules for using it.
+///
+/// Use [`Engine::rfc1924`], [`Engine::ascii85`], or [`Engine::z85`] for the predefined
+/// variants, or [`Engine::new`] with a custom [`Alphabet`].
+pub struct Engine {
+    pub(crate) alphabet: Alphabet,
+    pub(crate) variant: Variant,
+}
+
+impl Engine {
+    /// Pairs a custom `alphabet` with `variant`'s framing rules.
+    pub fn new(alphabet: Alphabet, variant: Variant) -> Engine {
+        Engine { alphabet, variant }
+    }
+
+    /// The RFC 1924 variant, matching [`crate::encode`]/[`crate::decode`].
+    pub fn rfc1924() -> Engine {
+        Engine::new(rfc1924_alphabet().clone(), Variant::Rfc1924)
+    }
+
+    /// Adobe/PostScript Ascii85: the 85 printable ASCII characters from `!` to `u`.
+    pub fn ascii85() -> Engine {
+        const fn symbols() -> [u8; 85] {
+            let mut symbols = [0u8; 85];
+            let mut i = 0;
+            while i < 85 {
+                symbols[i] = b'!' + i as u8;
+                i += 1;
+            }
+            symbols
+        }
+        const ALPHABET: Alphabet = Alphabet::new_const(symbols());
+        Engine::new(ALPHABET, Variant::Ascii85)
+    }
+
+    /// ZeroMQ's Z85.
+    pub fn z85() -> Engine {
+        const ALPHABET: Alphabet = Alphabet::new_const(
+            *b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#",
+        );
+        Engine::new(ALPHABET, Variant::Z85)
+    }
+}
+
+/// The powers of 85 needed to split a 32-bit group into its five base85 digits, computed once
+/// instead of via repeated `u32::pow` calls in the hot encode loop.
+const POW85_4: u32 = 85 * 85 * 85 * 85;
+const POW85_3: u32 = 85 * 85 * 85;
+const POW85_2: u32 = 85 * 85;
+
+pub(crate) fn encode_group_digits(alphabet: &Alphabet, decnum: u32) -> [u8; 5] {
+    let (d4, rem) = (decnum / POW85_4, decnum % POW85_4);
+    let (d3, rem) = (rem / POW85_3, rem % POW85_3);
+    let (d2, rem) = (rem / POW85_2, rem % POW85_2);
+    let (d1, d0) = (rem / 85, rem % 85);
+
+    [
+        alphabet.encode_digit(d4 as u8),
+        alphabet.encode_digit(d3 as u8),
+        alphabet.encode_digit(d2 as u8),
+        alphabet.encode_digit(d1 as u8),
+        alphabet.encode_digit(d0 as u8),
+    ]
+}
+
+/// Encodes `indata` using `engine`'s alphabet and framing rules.
+#[cfg(feature = "alloc")]
+pub fn encode_with(engine: &Engine, indata: &[u8]) -> String {
+    let mut out = String::new();
+    if engine.variant == Variant::Ascii85 {
+        out.push_str("<~");
+    }
+
+    let chunks = indata.chunks_exact(4);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        if engine.variant == Variant::Ascii85 && chunk == [0, 0, 0, 0] {
+            out.push('z');
+            continue;
+        }
+        let decnum = u32::from_be_bytes(<[u8; 4]>::try_from(chunk).unwrap());
+        let chars = encode_group_digits(&engine.alphabet, decnum);
+        out.push_str(core::str::from_utf8(&chars).unwrap());
+    }
+
+    if !remainder.is_empty() {
+        let mut padded = [0u8; 4];
+        padded[..remainder.len()].copy_from_slice(remainder);
+        let decnum = u32::from_be_bytes(padded);
+        let chars = encode_group_digits(&engine.alphabet, decnum);
+        out.push_str(core::str::from_utf8(&chars[..remainder.len() + 1]).unwrap());
+    }
+
+    if engine.variant == Variant::Ascii85 {
+        out.push_str("~>");
+    }
+    out
+}
+
+/// Decodes `instr` using `engine`'s alphabet and framing rules.
+///
+/// Reported error offsets are relative to `instr` with any `<~`/`~>` framing and leading/
+/// trailing whitespace already stripped, since those positions no longer exist in the content
+/// being decoded.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidByte`] for a symbol outside the alphabet (including a misplaced `z`
+/// shorthand), or [`Error::InvalidLength`] for a trailing group of exactly one character.
+#[cfg(feature = "alloc")]
+pub fn decode_with(engine: &Engine, instr: &str) -> Result<Vec<u8>> {
+    let mut s = instr.trim();
+    if engine.variant == Variant::Ascii85 {
+        s = s.strip_prefix("<~").unwrap_or(s);
+        s = s.strip_suffix("~>").unwrap_or(s);
+    }
+
+    let mut out = Vec::new();
+    let mut group = [0u8; 5];
+    let mut group_len = 0usize;
+    let mut group_offset = 0usize;
+
+    for (offset, c) in s.bytes().enumerate() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if engine.variant == Variant::Ascii85 && c == b'z' {
+            if group_len != 0 {
+                return Err(Error::InvalidByte { offset, byte: c });
+            }
+            out.extend_from_slice(&[0, 0, 0, 0]);
+            continue;
+        }
+        if group_len == 0 {
+            group_offset = offset;
+        }
+        let digit = engine
+            .alphabet
+            .decode_digit(c)
+            .ok_or(Error::InvalidByte { offset, byte: c })?;
+        group[group_len] = digit;
+        group_len += 1;
+        if group_len == 5 {
+            let accumulator = group.iter().fold(0u32, |acc, &d| acc * 85 + u32::from(d));
+            out.extend_from_slice(&accumulator.to_be_bytes());
+            group_len = 0;
+        }
+    }
+
+    match group_len {
+        0 => {}
+        1 => {
+            return Err(Error::InvalidLength {
+                offset: group_offset,
+            })
+        }
+        n => {
+            for slot in group.iter_mut().skip(n) {
+                *slot = 84;
+            }
+            let accumulator = group.iter().fold(0u32, |acc, &d| acc * 85 + u32::from(d));
+            let bytes = accumulator.to_be_bytes();
+            out.extend_from_slice(&bytes[..n - 1]);
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_bad_alphabets() {
+        let mut symbols = [b'0'; 85];
+        assert_eq!(
+            Alphabet::new(symbols).unwrap_err(),
+            Error::DuplicateAlphabetSymbol(b'0')
+        );
+
+        symbols = *b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}\n";
+        assert_eq!(
+            Alphabet::new(symbols).unwrap_err(),
+            Error::InvalidAlphabetSymbol(b'\n')
+        );
+    }
+
+    #[test]
+    fn test_rfc1924_roundtrip_matches_default() {
+        let engine = Engine::rfc1924();
+        for data in ["a", "aaaa", "aaaaaaaa", "hello, base85!"] {
+            let encoded = encode_with(&engine, data.as_bytes());
+            assert_eq!(encoded, crate::encode(data.as_bytes()));
+            assert_eq!(decode_with(&engine, &encoded).unwrap(), data.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_ascii85_roundtrip_and_zero_compression() {
+        let engine = Engine::ascii85();
+        for data in [&b""[..], b"a", b"aaaa", b"\0\0\0\0", b"\0\0\0\0hello", b"hello, base85!"] {
+            let encoded = encode_with(&engine, data);
+            assert_eq!(decode_with(&engine, &encoded).unwrap(), data);
+        }
+
+        let encoded = encode_with(&engine, &[0, 0, 0, 0]);
+        assert_eq!(encoded, "<~z~>");
+    }
+
+    #[test]
+    fn test_z85_roundtrip() {
+        let engine = Engine::z85();
+        let data = b"hello world!!!!";
+        let encoded = encode_with(&engine, data);
+        assert_eq!(decode_with(&engine, &encoded).unwrap(), data);
+    }
+}
@@ -0,0 +1,102 @@
+//! Allocation-free [`Display`](core::fmt::Display) formatting for base85 data.
+
+use core::fmt;
+
+use crate::alphabet::encode_group_digits;
+use crate::{rfc1924_alphabet, Alphabet, Engine, Variant};
+
+/// Formats a byte slice as base85 text straight into a [`core::fmt::Formatter`], without first
+/// collecting it into a `String`. Handy for logging or assembling JSON, where the encoded value
+/// is about to be written somewhere else anyway.
+///
+/// Defaults to the crate's RFC 1924 alphabet; use [`Base85Display::with`] to format with a
+/// different [`Engine`].
+pub struct Base85Display<'a> {
+    data: &'a [u8],
+    engine: Option<&'a Engine>,
+}
+
+impl<'a> Base85Display<'a> {
+    /// Formats `data` with the default RFC 1924 alphabet, matching [`crate::encode`].
+    pub fn new(data: &'a [u8]) -> Self {
+        Base85Display { data, engine: None }
+    }
+
+    /// Formats `data` with `engine`'s alphabet and framing rules.
+    pub fn with(engine: &'a Engine, data: &'a [u8]) -> Self {
+        Base85Display {
+            data,
+            engine: Some(engine),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Base85Display<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let default_alphabet;
+        let (alphabet, variant): (&Alphabet, Variant) = match self.engine {
+            Some(engine) => (&engine.alphabet, engine.variant),
+            None => {
+                default_alphabet = rfc1924_alphabet();
+                (default_alphabet, Variant::Rfc1924)
+            }
+        };
+
+        if variant == Variant::Ascii85 {
+            f.write_str("<~")?;
+        }
+
+        let chunks = self.data.chunks_exact(4);
+        let remainder = chunks.remainder();
+
+        for chunk in chunks {
+            if variant == Variant::Ascii85 && chunk == [0, 0, 0, 0] {
+                f.write_str("z")?;
+                continue;
+            }
+            let decnum = u32::from_be_bytes(<[u8; 4]>::try_from(chunk).unwrap());
+            let chars = encode_group_digits(alphabet, decnum);
+            f.write_str(core::str::from_utf8(&chars).unwrap())?;
+        }
+
+        if !remainder.is_empty() {
+            let mut padded = [0u8; 4];
+            padded[..remainder.len()].copy_from_slice(remainder);
+            let decnum = u32::from_be_bytes(padded);
+            let chars = encode_group_digits(alphabet, decnum);
+            f.write_str(core::str::from_utf8(&chars[..remainder.len() + 1]).unwrap())?;
+        }
+
+        if variant == Variant::Ascii85 {
+            f.write_str("~>")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_default_matches_encode() {
+        for data in ["a", "aaaa", "aaaaaaaa", "hello, base85!"] {
+            assert_eq!(
+                format!("{}", Base85Display::new(data.as_bytes())),
+                crate::encode(data.as_bytes())
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_matches_encode_with() {
+        let engine = Engine::ascii85();
+        for data in [&b""[..], b"a", b"aaaa", b"\0\0\0\0", b"hello, base85!"] {
+            assert_eq!(
+                format!("{}", Base85Display::with(&engine, data)),
+                crate::encode_with(&engine, data)
+            );
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Streaming `Read`/`Write` adapters for encoding and decoding base85 without holding the
+//! whole input in memory at once.
+
+use std::io::{self, Read, Write};
+
+use crate::alphabet::encode_group_digits;
+use crate::{rfc1924_alphabet, Error};
+
+/// Wraps a [`Write`] and base85-encodes every byte written through it before passing the
+/// encoded characters along to the inner writer.
+///
+/// Input is buffered into 4-byte groups; each full group is encoded into 5 characters and
+/// written through immediately. A trailing short group (1-3 bytes) is held until
+/// [`finish`](EncoderWriter::finish) is called or the adapter is dropped.
+pub struct EncoderWriter<W: Write> {
+    inner: Option<W>,
+    buf: [u8; 4],
+    buf_len: usize,
+}
+
+impl<W: Write> EncoderWriter<W> {
+    /// Creates a new encoder wrapping `inner`.
+    pub fn new(inner: W) -> Self {
+        EncoderWriter {
+            inner: Some(inner),
+            buf: [0; 4],
+            buf_len: 0,
+        }
+    }
+
+    /// Flushes any buffered trailing bytes as a short group and returns the wrapped writer.
+    ///
+    /// Dropping the adapter without calling this does the same flush, but can't report errors.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush_tail()?;
+        Ok(self.inner.take().expect("finish() called more than once"))
+    }
+
+    fn flush_tail(&mut self) -> io::Result<()> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok(());
+        };
+        if self.buf_len > 0 {
+            let chars = encode_group(&self.buf, self.buf_len);
+            inner.write_all(&chars[..self.buf_len + 1])?;
+            self.buf_len = 0;
+        }
+        inner.flush()
+    }
+}
+
+impl<W: Write> Write for EncoderWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let inner = self.inner.as_mut().expect("write() called after finish()");
+        let written = data.len();
+        let mut data = data;
+
+        if self.buf_len > 0 {
+            let take = (4 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+
+            if self.buf_len < 4 {
+                return Ok(written);
+            }
+            inner.write_all(&encode_group(&self.buf, 4))?;
+            self.buf_len = 0;
+        }
+
+        let mut chunks = data.chunks_exact(4);
+        for chunk in &mut chunks {
+            let group: [u8; 4] = chunk.try_into().unwrap();
+            inner.write_all(&encode_group(&group, 4))?;
+        }
+
+        let remainder = chunks.remainder();
+        self.buf[..remainder.len()].copy_from_slice(remainder);
+        self.buf_len = remainder.len();
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner
+            .as_mut()
+            .expect("flush() called after finish()")
+            .flush()
+    }
+}
+
+impl<W: Write> Drop for EncoderWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush_tail();
+    }
+}
+
+/// Encodes the first `len` bytes of `group` (1-4 of them) into up to 5 base85 characters,
+/// padding the unused tail with zero bytes the same way [`crate::encode`] does.
+fn encode_group(group: &[u8; 4], len: usize) -> [u8; 5] {
+    let mut padded = [0u8; 4];
+    padded[..len].copy_from_slice(&group[..len]);
+    let decnum = u32::from_be_bytes(padded);
+    encode_group_digits(rfc1924_alphabet(), decnum)
+}
+
+/// Wraps a [`Read`] of base85 text and yields decoded bytes, pulling more input only as needed.
+///
+/// ASCII whitespace (`\n`, `\r`, `\t`, form feed, space) in the input is skipped, matching
+/// [`crate::decode`].
+pub struct DecoderReader<R: Read> {
+    inner: R,
+    pos: usize,
+    chars: [u8; 5],
+    char_offsets: [usize; 5],
+    chars_len: usize,
+    input_done: bool,
+    out: [u8; 4],
+    out_len: usize,
+    out_pos: usize,
+}
+
+impl<R: Read> DecoderReader<R> {
+    /// Creates a new decoder pulling base85 text from `inner`.
+    pub fn new(inner: R) -> Self {
+        DecoderReader {
+            inner,
+            pos: 0,
+            chars: [0; 5],
+            char_offsets: [0; 5],
+            chars_len: 0,
+            input_done: false,
+            out: [0; 4],
+            out_len: 0,
+            out_pos: 0,
+        }
+    }
+
+    /// Reads non-whitespace characters from the inner reader until a 5-char group is
+    /// collected or the input is exhausted.
+    fn fill_group(&mut self) -> io::Result<()> {
+        let mut byte = [0u8; 1];
+        while self.chars_len < 5 {
+            if self.inner.read(&mut byte)? == 0 {
+                self.input_done = true;
+                break;
+            }
+            let offset = self.pos;
+            self.pos += 1;
+            if !byte[0].is_ascii_whitespace() {
+                self.chars[self.chars_len] = byte[0];
+                self.char_offsets[self.chars_len] = offset;
+                self.chars_len += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes the currently buffered group (full or short) into `self.out`.
+    fn decode_group(&mut self) -> io::Result<()> {
+        if self.chars_len == 0 {
+            self.out_len = 0;
+            return Ok(());
+        }
+        if self.chars_len == 1 {
+            return Err(io_err(Error::InvalidLength {
+                offset: self.char_offsets[0],
+            }));
+        }
+
+        let alphabet = rfc1924_alphabet();
+        let digit = |i: usize| -> Result<u8, Error> {
+            if i < self.chars_len {
+                alphabet
+                    .decode_digit(self.chars[i])
+                    .ok_or(Error::InvalidByte {
+                        offset: self.char_offsets[i],
+                        byte: self.chars[i],
+                    })
+            } else {
+                Ok(84)
+            }
+        };
+
+        let mut accumulator = 0u32;
+        for i in 0..5 {
+            accumulator = accumulator * 85 + u32::from(digit(i).map_err(io_err)?);
+        }
+
+        self.out = accumulator.to_be_bytes();
+        self.out_len = self.chars_len - 1;
+        self.out_pos = 0;
+        self.chars_len = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecoderReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.out_pos >= self.out_len {
+            if self.input_done {
+                return Ok(0);
+            }
+            self.fill_group()?;
+            self.decode_group()?;
+            if self.out_len == 0 {
+                return Ok(0);
+            }
+        }
+
+        let n = (self.out_len - self.out_pos).min(buf.len());
+        buf[..n].copy_from_slice(&self.out[self.out_pos..self.out_pos + n]);
+        self.out_pos += n;
+        Ok(n)
+    }
+}
+
+fn io_err(e: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
@@ -8,176 +8,279 @@
 //!
 //! ## Usage
 //!
-//! This was my first real Rust project but has matured since then and is stable. The API is simple: `encode()` turns a slice of bytes into a String and `decode()` turns a string reference into a Vector of bytes (u8). Both calls work completely within RAM, so processing huge files is probably not a good idea.
+//! This was my first real Rust project but has matured since then and is stable. The API is simple: `encode()` turns a slice of bytes into a String and `decode()` turns a string reference into a Vector of bytes (u8). Both calls work completely within RAM, so processing huge files is probably not a good idea; for that, use the streaming [`EncoderWriter`] and [`DecoderReader`] adapters instead, which work in small fixed-size chunks. If you just want to format encoded data straight into a `String` or log line without allocating an intermediate one, use [`Base85Display`] instead. The free functions always use the RFC 1924 variant; Ascii85 and Z85 are available through [`encode_with`]/[`decode_with`] with an [`Engine`].
 //!
 //! ## Contributions
 //!
 //! Even though I've been coding for a while and have learned quite a bit about Rust, I'm still a novice. Suggestions and contributions are always welcome and appreciated.
+//!
+//! ## `no_std`
+//!
+//! This crate is `#![no_std]`. The slice-based transforms (`encode_slice`/`decode_slice` and
+//! friends) and the [`Alphabet`]/[`Engine`] machinery work on bare `core`. `encode`/`decode` and
+//! the rest of the `String`/`Vec`-returning API need an allocator and live behind the `alloc`
+//! feature; [`EncoderWriter`]/[`DecoderReader`] need actual `std::io` and live behind `std`.
+
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+mod alphabet;
+mod display;
+#[cfg(feature = "std")]
+mod io;
 
-use core::mem::MaybeUninit;
+#[cfg(feature = "alloc")]
+pub use crate::alphabet::{decode_with, encode_with};
+use crate::alphabet::{encode_group_digits, rfc1924_alphabet};
+pub use crate::alphabet::{Alphabet, Engine, Variant};
+pub use crate::display::Base85Display;
+#[cfg(feature = "std")]
+pub use crate::io::{DecoderReader, EncoderWriter};
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
-#[derive(thiserror::Error, Debug)]
+/// The error type for this crate's fallible operations.
+#[derive(Debug, PartialEq)]
 pub enum Error {
-    #[error("Unexpected end of input")]
     UnexpectedEof,
-    #[error("Unexpected character '{0}'")]
-    InvalidCharacter(u8),
+    InvalidByte { offset: usize, byte: u8 },
+    InvalidLength { offset: usize },
+    BufferTooSmall { needed: usize, provided: usize },
+    InvalidAlphabetSymbol(u8),
+    DuplicateAlphabetSymbol(u8),
 }
 
-#[inline]
-fn byte_to_char85(x85: u8) -> u8 {
-    static B85_TO_CHAR: &[u8] =
-        b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
-    B85_TO_CHAR[x85 as usize]
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            Error::UnexpectedEof => write!(f, "Unexpected end of input"),
+            Error::InvalidByte { offset, byte } => {
+                write!(f, "invalid byte '{}' at offset {offset}", byte as char)
+            }
+            Error::InvalidLength { offset } => {
+                write!(f, "invalid trailing group of length 1 at offset {offset}")
+            }
+            Error::BufferTooSmall { needed, provided } => write!(
+                f,
+                "output buffer too small: needed {needed} bytes, got {provided}"
+            ),
+            Error::InvalidAlphabetSymbol(c) => {
+                write!(f, "alphabet symbol '{}' is not printable ASCII", c as char)
+            }
+            Error::DuplicateAlphabetSymbol(c) => {
+                write!(f, "duplicate alphabet symbol '{}'", c as char)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Computes the length in bytes that [`encode`]/[`encode_slice`] will produce for `n` bytes of
+/// input, without actually encoding anything. Useful for sizing a buffer ahead of time.
+pub fn encoded_len(n: usize) -> usize {
+    let remainder = n % 4;
+    if remainder == 0 {
+        (n / 4) * 5
+    } else {
+        (n / 4) * 5 + remainder + 1
+    }
 }
 
-#[inline]
-fn char85_to_byte(c: u8) -> Result<u8> {
-    match c {
-        b'0'..=b'9' => Ok(c - b'0'),
-        b'A'..=b'Z' => Ok(c - b'A' + 10),
-        b'a'..=b'z' => Ok(c - b'a' + 36),
-        b'!' => Ok(62),
-        b'#' => Ok(63),
-        b'$' => Ok(64),
-        b'%' => Ok(65),
-        b'&' => Ok(66),
-        b'(' => Ok(67),
-        b')' => Ok(68),
-        b'*' => Ok(69),
-        b'+' => Ok(70),
-        b'-' => Ok(71),
-        b';' => Ok(72),
-        b'<' => Ok(73),
-        b'=' => Ok(74),
-        b'>' => Ok(75),
-        b'?' => Ok(76),
-        b'@' => Ok(77),
-        b'^' => Ok(78),
-        b'_' => Ok(79),
-        b'`' => Ok(80),
-        b'{' => Ok(81),
-        b'|' => Ok(82),
-        b'}' => Ok(83),
-        b'~' => Ok(84),
-        v => Err(Error::InvalidCharacter(v)),
+/// Computes the length in bytes that [`decode`]/[`decode_slice`] will produce for an `encoded`
+/// string, without actually decoding anything. ASCII whitespace in `encoded` doesn't count
+/// toward the length, since [`decode`] skips it.
+pub fn decoded_len(encoded: &str) -> usize {
+    let n = encoded.bytes().filter(|b| !b.is_ascii_whitespace()).count();
+    let remainder = n % 5;
+    if remainder == 0 {
+        (n / 5) * 4
+    } else {
+        (n / 5) * 4 + remainder - 1
     }
 }
 
 /// encode() turns a slice of bytes into a string of encoded data
+#[cfg(feature = "alloc")]
 pub fn encode(indata: &[u8]) -> String {
-    let chunks = indata.chunks_exact(4);
-    let remainder = chunks.remainder();
-    let capacity = if remainder.is_empty() {
-        (indata.len() / 4) * 5
-    } else {
-        (indata.len() / 4) * 5 + remainder.len() + 1
-    };
-    let mut out = Vec::<MaybeUninit<u8>>::with_capacity(capacity);
-    unsafe {
-        out.set_len(capacity);
-    }
-    let mut out_chunks = out.chunks_exact_mut(5);
-
-    for (chunk, out) in std::iter::zip(chunks, &mut out_chunks) {
-        let decnum = u32::from_be_bytes(<[u8; 4]>::try_from(chunk).unwrap());
-        out[0] = MaybeUninit::new(byte_to_char85((decnum / 85u32.pow(4)) as u8));
-        out[1] = MaybeUninit::new(byte_to_char85(
-            ((decnum % 85u32.pow(4)) / 85u32.pow(3)) as u8,
-        ));
-        out[2] = MaybeUninit::new(byte_to_char85(
-            ((decnum % 85u32.pow(3)) / 85u32.pow(2)) as u8,
-        ));
-        out[3] = MaybeUninit::new(byte_to_char85(((decnum % 85u32.pow(2)) / 85u32) as u8));
-        out[4] = MaybeUninit::new(byte_to_char85((decnum % 85u32) as u8));
-    }
-
-    let out_remainder = out_chunks.into_remainder();
-    if let Some(a) = remainder.first().copied() {
-        let b = remainder.get(1).copied();
-        let c = remainder.get(2).copied();
-        let d = remainder.get(3).copied();
+    let mut out = vec![0u8; encoded_len(indata.len())];
+    let written = encode_slice(indata, &mut out).expect("buffer sized via encoded_len");
+    debug_assert_eq!(written, out.len());
+    // SAFETY: encode_slice() always fills every byte it's given with an ASCII base85 character.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+/// decode() turns a string of encoded data into a slice of bytes
+#[cfg(feature = "alloc")]
+pub fn decode(instr: &str) -> Result<Vec<u8>> {
+    let mut out = vec![0u8; decoded_len(instr)];
+    let written = decode_slice(instr, &mut out)?;
+    debug_assert_eq!(written, out.len());
+    Ok(out)
+}
+
+/// Encodes `input` into `out`, returning the number of bytes written, without allocating.
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if `out` is shorter than [`encoded_len`]`(input.len())`.
+pub fn encode_slice(input: &[u8], out: &mut [u8]) -> Result<usize> {
+    let needed = encoded_len(input.len());
+    if out.len() < needed {
+        return Err(Error::BufferTooSmall {
+            needed,
+            provided: out.len(),
+        });
+    }
+    let out = &mut out[..needed];
+    let alphabet = rfc1924_alphabet();
+
+    // Process several groups per outer iteration, so chunks_exact()'s bounds check is paid once
+    // per block rather than once per group, and the compiler has a tight inner loop to unroll.
+    const GROUPS_PER_BLOCK: usize = 8;
+    let mut blocks = input.chunks_exact(4 * GROUPS_PER_BLOCK);
+    let mut out_blocks = out.chunks_exact_mut(5 * GROUPS_PER_BLOCK);
+    for (block, out_block) in core::iter::zip(&mut blocks, &mut out_blocks) {
+        for (group, out_group) in
+            core::iter::zip(block.chunks_exact(4), out_block.chunks_exact_mut(5))
+        {
+            let decnum = u32::from_be_bytes(<[u8; 4]>::try_from(group).unwrap());
+            out_group.copy_from_slice(&encode_group_digits(alphabet, decnum));
+        }
+    }
+
+    let mut groups = blocks.remainder().chunks_exact(4);
+    let mut out_groups = out_blocks.into_remainder().chunks_exact_mut(5);
+    for (group, out_group) in core::iter::zip(&mut groups, &mut out_groups) {
+        let decnum = u32::from_be_bytes(<[u8; 4]>::try_from(group).unwrap());
+        out_group.copy_from_slice(&encode_group_digits(alphabet, decnum));
+    }
+
+    let tail = groups.remainder();
+    let out_tail = out_groups.into_remainder();
+    if let Some(&a) = tail.first() {
+        let b = tail.get(1).copied();
+        let c = tail.get(2).copied();
+        let d = tail.get(3).copied();
         let decnum = u32::from_be_bytes([a, b.unwrap_or(0), c.unwrap_or(0), d.unwrap_or(0)]);
-        out_remainder[0] = MaybeUninit::new(byte_to_char85((decnum / 85u32.pow(4)) as u8));
-        out_remainder[1] = MaybeUninit::new(byte_to_char85(
-            ((decnum % 85u32.pow(4)) / 85u32.pow(3)) as u8,
-        ));
-        if b.is_some() {
-            out_remainder[2] = MaybeUninit::new(byte_to_char85(
-                ((decnum % 85u32.pow(3)) / 85u32.pow(2)) as u8,
-            ));
+        out_tail.copy_from_slice(&encode_group_digits(alphabet, decnum)[..tail.len() + 1]);
+    }
+
+    Ok(needed)
+}
+
+/// Decodes `input` into `out`, returning the number of bytes written, without allocating.
+///
+/// ASCII whitespace in `input` is skipped, like [`decode`]. On failure, the reported offset
+/// points at the true position of the bad byte in `input`, counting skipped whitespace.
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if `out` is shorter than [`decoded_len`]`(input)`, or an
+/// [`Error::InvalidByte`]/[`Error::InvalidLength`] if `input` isn't valid base85.
+pub fn decode_slice(input: &str, out: &mut [u8]) -> Result<usize> {
+    let needed = decoded_len(input);
+    if out.len() < needed {
+        return Err(Error::BufferTooSmall {
+            needed,
+            provided: out.len(),
+        });
+    }
+
+    let alphabet = rfc1924_alphabet();
+    let mut out_pos = 0;
+    let mut group = [0u8; 5];
+    let mut group_len = 0usize;
+    let mut group_offset = 0usize;
+
+    for (offset, &c) in input.as_bytes().iter().enumerate() {
+        if c.is_ascii_whitespace() {
+            continue;
         }
-        if c.is_some() {
-            out_remainder[3] =
-                MaybeUninit::new(byte_to_char85(((decnum % 85u32.pow(2)) / 85u32) as u8));
+        if group_len == 0 {
+            group_offset = offset;
         }
-        if d.is_some() {
-            out_remainder[4] = MaybeUninit::new(byte_to_char85((decnum % 85u32) as u8));
+        let digit = alphabet
+            .decode_digit(c)
+            .ok_or(Error::InvalidByte { offset, byte: c })?;
+        group[group_len] = digit;
+        group_len += 1;
+        if group_len == 5 {
+            let accumulator = group.iter().fold(0u32, |acc, &d| acc * 85 + u32::from(d));
+            out[out_pos..out_pos + 4].copy_from_slice(&accumulator.to_be_bytes());
+            out_pos += 4;
+            group_len = 0;
         }
     }
 
-    unsafe { String::from_utf8_unchecked(std::mem::transmute::<_, Vec<u8>>(out)) }
+    match group_len {
+        0 => {}
+        1 => {
+            return Err(Error::InvalidLength {
+                offset: group_offset,
+            })
+        }
+        n => {
+            for slot in group.iter_mut().skip(n) {
+                *slot = 84;
+            }
+            let accumulator = group.iter().fold(0u32, |acc, &d| acc * 85 + u32::from(d));
+            let bytes = accumulator.to_be_bytes();
+            out[out_pos..out_pos + n - 1].copy_from_slice(&bytes[..n - 1]);
+            out_pos += n - 1;
+        }
+    }
+
+    Ok(out_pos)
 }
 
-/// decode() turns a string of encoded data into a slice of bytes
-pub fn decode(instr: &str) -> Result<Vec<u8>> {
-    let indata = instr.as_bytes();
-    let chunks = indata.chunks_exact(5);
-    let remainder = chunks.remainder();
-    let capacity = if remainder.is_empty() {
-        (indata.len() / 5) * 4
-    } else {
-        (indata.len() / 5) * 4 + remainder.len() - 1
-    };
-    let mut out = Vec::<MaybeUninit<u8>>::with_capacity(capacity);
+/// Encodes `input` and appends the result to `out`, reusing `out`'s existing allocation.
+#[cfg(feature = "alloc")]
+pub fn encode_buf(input: &[u8], out: &mut String) {
+    let start = out.len();
+    let needed = encoded_len(input.len());
+    // SAFETY: encode_slice() always fills every byte it's given with an ASCII base85 character.
     unsafe {
-        out.set_len(capacity);
-    }
-    let mut out_chunks = out.chunks_exact_mut(4);
-
-    for (chunk, out_chunk) in std::iter::zip(chunks, &mut out_chunks) {
-        let accumulator = u32::from(char85_to_byte(chunk[0])?) * 85u32.pow(4)
-            + u32::from(char85_to_byte(chunk[1])?) * 85u32.pow(3)
-            + u32::from(char85_to_byte(chunk[2])?) * 85u32.pow(2)
-            + u32::from(char85_to_byte(chunk[3])?) * 85u32
-            + u32::from(char85_to_byte(chunk[4])?);
-        out_chunk[0] = MaybeUninit::new((accumulator >> 24) as u8);
-        out_chunk[1] = MaybeUninit::new((accumulator >> 16) as u8);
-        out_chunk[2] = MaybeUninit::new((accumulator >> 8) as u8);
-        out_chunk[3] = MaybeUninit::new(accumulator as u8);
-    }
-
-    let out_remainder = out_chunks.into_remainder();
-    if let Some(a) = remainder.first().copied() {
-        let b = remainder.get(1).copied();
-        let c = remainder.get(2).copied();
-        let d = remainder.get(3).copied();
-        let e = remainder.get(4).copied();
-        let accumulator = u32::from(char85_to_byte(a)?) * 85u32.pow(4)
-            + u32::from(b.map_or(Err(Error::UnexpectedEof), char85_to_byte)?) * 85u32.pow(3)
-            + u32::from(c.map_or(Ok(126), char85_to_byte)?) * 85u32.pow(2)
-            + u32::from(d.map_or(Ok(126), char85_to_byte)?) * 85u32.pow(1)
-            + u32::from(e.map_or(Ok(126), char85_to_byte)?) * 85u32.pow(0);
-        out_remainder[0] = MaybeUninit::new((accumulator >> 24) as u8);
-        if remainder.len() > 2 {
-            out_remainder[1] = MaybeUninit::new((accumulator >> 16) as u8);
-            if remainder.len() > 3 {
-                out_remainder[2] = MaybeUninit::new((accumulator >> 8) as u8);
-                if remainder.len() > 4 {
-                    out_remainder[3] = MaybeUninit::new(accumulator as u8);
-                }
-            }
-        }
+        let buf = out.as_mut_vec();
+        buf.resize(start + needed, 0);
+        encode_slice(input, &mut buf[start..]).expect("buffer sized via encoded_len");
     }
+}
 
-    Ok(unsafe { std::mem::transmute::<_, Vec<u8>>(out) })
+/// Decodes `input` and appends the result to `out`, reusing `out`'s existing allocation.
+///
+/// # Errors
+///
+/// Returns an error if `input` isn't valid base85. `out` is left at its original length in
+/// that case.
+#[cfg(feature = "alloc")]
+pub fn decode_buf(input: &str, out: &mut Vec<u8>) -> Result<()> {
+    let start = out.len();
+    let needed = decoded_len(input);
+    out.resize(start + needed, 0);
+    if let Err(e) = decode_slice(input, &mut out[start..]) {
+        out.truncate(start);
+        return Err(e);
+    }
+    Ok(())
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use crate::*;
+    use alloc::format;
 
     #[test]
     fn test_encode_decode() {
@@ -220,4 +323,81 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_slice_and_buf() {
+        let testlist = [
+            ("a", "VE"),
+            ("aaaa", "VPRom"),
+            ("aaaaaaaa", "VPRomVPRom"),
+        ];
+
+        for test in testlist.iter() {
+            let mut encoded = vec![0u8; encoded_len(test.0.len())];
+            let written = encode_slice(test.0.as_bytes(), &mut encoded).unwrap();
+            assert_eq!(written, test.1.len());
+            assert_eq!(std::str::from_utf8(&encoded).unwrap(), test.1);
+
+            assert_eq!(encode_slice(test.0.as_bytes(), &mut [0u8; 1]), Err(Error::BufferTooSmall {
+                needed: test.1.len(),
+                provided: 1,
+            }));
+
+            let mut decoded = vec![0u8; decoded_len(test.1)];
+            let written = decode_slice(test.1, &mut decoded).unwrap();
+            assert_eq!(written, test.0.len());
+            assert_eq!(decoded, test.0.as_bytes());
+
+            let mut buf = String::from("prefix-");
+            encode_buf(test.0.as_bytes(), &mut buf);
+            assert_eq!(buf, format!("prefix-{}", test.1));
+
+            let mut buf = vec![1u8, 2, 3];
+            decode_buf(test.1, &mut buf).unwrap();
+            assert_eq!(buf, [&[1u8, 2, 3][..], test.0.as_bytes()].concat());
+        }
+    }
+
+    #[test]
+    fn test_decode_error_offsets() {
+        assert_eq!(
+            decode("VP.om").unwrap_err(),
+            Error::InvalidByte {
+                offset: 2,
+                byte: b'.'
+            }
+        );
+
+        assert_eq!(
+            decode("VPRomV").unwrap_err(),
+            Error::InvalidLength { offset: 5 }
+        );
+    }
+
+    /// A small xorshift PRNG, used instead of pulling in a "proptest"-style dependency just for
+    /// this one round-trip test.
+    struct Xorshift(u64);
+
+    impl Xorshift {
+        fn next_u8(&mut self) -> u8 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 & 0xff) as u8
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_property() {
+        let mut rng = Xorshift(0x2545_f491_4f6c_dd1d);
+
+        // Cover every length mod 4 many times over, including the empty input.
+        for len in 0..=263usize {
+            let data: Vec<u8> = (0..len).map(|_| rng.next_u8()).collect();
+            let encoded = encode(&data);
+            let decoded = decode(&encoded)
+                .unwrap_or_else(|e| panic!("roundtrip decode failed for length {len}: {e}"));
+            assert_eq!(decoded, data, "roundtrip mismatch for length {len}");
+        }
+    }
 }